@@ -11,7 +11,9 @@ use {
     super::standalone_distribution::DistributionExtensionModule,
     crate::app_packaging::resource::{FileContent, FileManifest},
     anyhow::{anyhow, Result},
+    flate2::{write::ZlibEncoder, Compression},
     python_packaging::bytecode::{BytecodeCompiler, CompileMode},
+    python_packaging::licensing::{LicensedComponent, LicensedComponents},
     python_packaging::module_util::packages_from_module_names,
     python_packaging::resource::{
         BytecodeOptimizationLevel, DataLocation, PythonExtensionModule,
@@ -33,6 +35,347 @@ use {
     std::path::{Path, PathBuf},
 };
 
+/// Controls how embedded relative-path `.pyc` files are validated against their source.
+///
+/// This mirrors the invalidation tags CPython's importer understands for on-disk
+/// bytecode: a timestamp-based `.pyc` is re-compiled whenever the source's mtime/size
+/// changes, a hash-based `.pyc` is re-compiled whenever the source's hash changes, and
+/// an unchecked hash-based `.pyc` is trusted without ever consulting the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BytecodeInvalidationMode {
+    /// Validate bytecode against the source file's mtime and size.
+    Timestamp,
+    /// Validate bytecode against a hash of the source file.
+    CheckedHash,
+    /// Trust the bytecode without validating it against the source file.
+    UncheckedHash,
+}
+
+impl Default for BytecodeInvalidationMode {
+    fn default() -> Self {
+        BytecodeInvalidationMode::UncheckedHash
+    }
+}
+
+/// Resolve the 4 byte `.pyc` magic number for a given bytecode cache tag.
+fn pyc_magic_number(cache_tag: &str) -> Result<[u8; 4]> {
+    let number: u16 = match cache_tag {
+        "cpython-35" => 3351,
+        "cpython-36" => 3379,
+        "cpython-37" => 3394,
+        "cpython-38" => 3413,
+        "cpython-39" => 3425,
+        _ => return Err(anyhow!("unable to resolve pyc magic number for cache tag {}", cache_tag)),
+    };
+
+    let mut magic = [0u8; 4];
+    magic[0..2].copy_from_slice(&number.to_le_bytes());
+    magic[2..4].copy_from_slice(b"\r\n");
+
+    Ok(magic)
+}
+
+/// One SipHash compression round.
+fn sip_round(v0: &mut u64, v1: &mut u64, v2: &mut u64, v3: &mut u64) {
+    *v0 = v0.wrapping_add(*v1);
+    *v1 = v1.rotate_left(13);
+    *v1 ^= *v0;
+    *v0 = v0.rotate_left(32);
+    *v2 = v2.wrapping_add(*v3);
+    *v3 = v3.rotate_left(16);
+    *v3 ^= *v2;
+    *v0 = v0.wrapping_add(*v3);
+    *v3 = v3.rotate_left(21);
+    *v3 ^= *v0;
+    *v2 = v2.wrapping_add(*v1);
+    *v1 = v1.rotate_left(17);
+    *v1 ^= *v2;
+    *v2 = v2.rotate_left(32);
+}
+
+/// SipHash-2-4 (two compression rounds, four finalization rounds) keyed the way CPython's
+/// `_Py_KeyedHash(key, ...)` keys it: `k0 = key`, `k1 = 0`. Note this only XORs `key` into
+/// `v0`/`v2`; `v1`/`v3` keep their plain initialization constants since `k1` is zero.
+///
+/// CPython 3.5 through 3.9 (the cache tags this module supports) build with SipHash-2-4 as
+/// `Py_HASH_ALGORITHM`; SipHash-1-3 only became the default starting with 3.11.
+fn py_keyed_hash(key: u64, data: &[u8]) -> u64 {
+    let mut v0 = 0x736f_6d65_7073_6575u64 ^ key;
+    let mut v1 = 0x646f_7261_6e64_6f6du64;
+    let mut v2 = 0x6c79_6765_6e65_7261u64 ^ key;
+    let mut v3 = 0x7465_6462_7974_6573u64;
+
+    let chunks = data.chunks_exact(8);
+    let remainder = chunks.remainder();
+
+    for chunk in chunks {
+        let m = u64::from_le_bytes(chunk.try_into().expect("chunk is 8 bytes"));
+        v3 ^= m;
+        sip_round(&mut v0, &mut v1, &mut v2, &mut v3);
+        sip_round(&mut v0, &mut v1, &mut v2, &mut v3);
+        v0 ^= m;
+    }
+
+    let mut last_block = [0u8; 8];
+    last_block[..remainder.len()].copy_from_slice(remainder);
+    last_block[7] = (data.len() & 0xff) as u8;
+    let m = u64::from_le_bytes(last_block);
+
+    v3 ^= m;
+    sip_round(&mut v0, &mut v1, &mut v2, &mut v3);
+    sip_round(&mut v0, &mut v1, &mut v2, &mut v3);
+    v0 ^= m;
+
+    v2 ^= 0xff;
+    sip_round(&mut v0, &mut v1, &mut v2, &mut v3);
+    sip_round(&mut v0, &mut v1, &mut v2, &mut v3);
+    sip_round(&mut v0, &mut v1, &mut v2, &mut v3);
+    sip_round(&mut v0, &mut v1, &mut v2, &mut v3);
+
+    (v0 ^ v1) ^ (v2 ^ v3)
+}
+
+/// Compute the 8 byte truncated source hash used by hash-based `.pyc` files.
+///
+/// This mirrors CPython's `_imp.source_hash`, which `importlib.util.source_hash` and the
+/// hash-based `.pyc` loader both use: `_Py_KeyedHash` (SipHash-2-4 for the cache tags this
+/// module supports) over the raw source bytes, keyed with the interpreter's own raw magic
+/// number. Hashing with anything else (e.g. a generic Rust hasher, the wrong SipHash variant,
+/// or the wrong keying) produces a `.pyc` that CPython can never validate, so a `CheckedHash`
+/// module would be treated as permanently stale.
+fn source_hash(magic: &[u8; 4], source: &[u8]) -> [u8; 8] {
+    let key = u64::from(u32::from_le_bytes(*magic));
+
+    py_keyed_hash(key, source).to_le_bytes()
+}
+
+/// Build the 16 byte `.pyc` header for the given invalidation mode.
+fn pyc_header(
+    mode: BytecodeInvalidationMode,
+    cache_tag: &str,
+    source: &[u8],
+    source_mtime: u32,
+    source_size: u32,
+) -> Result<[u8; 16]> {
+    let mut header = [0u8; 16];
+    let magic = pyc_magic_number(cache_tag)?;
+    header[0..4].copy_from_slice(&magic);
+
+    match mode {
+        BytecodeInvalidationMode::Timestamp => {
+            header[4..8].copy_from_slice(&0u32.to_le_bytes());
+            header[8..12].copy_from_slice(&source_mtime.to_le_bytes());
+            header[12..16].copy_from_slice(&source_size.to_le_bytes());
+        }
+        BytecodeInvalidationMode::CheckedHash => {
+            header[4..8].copy_from_slice(&3u32.to_le_bytes());
+            header[8..16].copy_from_slice(&source_hash(&magic, source));
+        }
+        BytecodeInvalidationMode::UncheckedHash => {
+            header[4..8].copy_from_slice(&1u32.to_le_bytes());
+            header[8..16].copy_from_slice(&source_hash(&magic, source));
+        }
+    }
+
+    Ok(header)
+}
+
+/// System libraries assumed to ship with the target OS and never treated as copyleft.
+const SAFE_SYSTEM_LIBRARIES: &[&str] = &["c", "m", "dl", "pthread", "rt", "util"];
+
+/// Policy governing which builtin distribution extension modules are accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtensionModuleFilter {
+    /// Only keep extension modules required to initialize an interpreter.
+    Minimal,
+    /// Keep every extension module.
+    All,
+    /// Reject any extension module that links against a library.
+    NoLibraries,
+    /// Reject any extension module that links against a copyleft-licensed library.
+    NoCopyleft,
+}
+
+impl Default for ExtensionModuleFilter {
+    fn default() -> Self {
+        ExtensionModuleFilter::All
+    }
+}
+
+/// Well-known Visual C++ runtime DLLs that Python extension modules commonly depend on.
+const WINDOWS_RUNTIME_DLL_NAMES: &[&str] = &[
+    "vcruntime140.dll",
+    "vcruntime140_1.dll",
+    "msvcp140.dll",
+    "concrt140.dll",
+];
+
+/// Controls whether Visual C++ runtime DLLs are copied next to the produced binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowsRuntimeDllsMode {
+    /// Never attempt to resolve or bundle runtime DLLs.
+    Never,
+    /// Bundle a runtime DLL only if it is actually referenced by the PE import table of a
+    /// shared library installed relative to the binary.
+    WhenPresent,
+    /// Always bundle the full set of known runtime DLLs.
+    Always,
+}
+
+impl Default for WindowsRuntimeDllsMode {
+    fn default() -> Self {
+        WindowsRuntimeDllsMode::Never
+    }
+}
+
+/// Extract the names of DLLs imported by a Windows PE binary.
+///
+/// This walks the PE import directory rather than shelling out to a tool so it works the
+/// same way on every host platform doing the packaging.
+fn pe_import_dll_names(data: &[u8]) -> Result<Vec<String>> {
+    if data.len() < 0x40 || &data[0..2] != b"MZ" {
+        return Err(anyhow!("not a valid PE file"));
+    }
+
+    let e_lfanew = u32::from_le_bytes(data[0x3c..0x40].try_into()?) as usize;
+
+    if data.len() < e_lfanew + 24 || &data[e_lfanew..e_lfanew + 4] != b"PE\0\0" {
+        return Err(anyhow!("missing PE signature"));
+    }
+
+    let coff = e_lfanew + 4;
+    let number_of_sections = u16::from_le_bytes(data[coff + 2..coff + 4].try_into()?) as usize;
+    let size_of_optional_header = u16::from_le_bytes(data[coff + 16..coff + 18].try_into()?) as usize;
+    let optional_header = coff + 20;
+    let magic = u16::from_le_bytes(data[optional_header..optional_header + 2].try_into()?);
+
+    let data_directory = match magic {
+        0x10b => optional_header + 96,
+        0x20b => optional_header + 112,
+        _ => return Err(anyhow!("unrecognized PE optional header magic {:#x}", magic)),
+    };
+
+    // Data directory index 1 is the import directory.
+    let import_directory = data_directory + 8;
+    let import_rva = u32::from_le_bytes(
+        data[import_directory..import_directory + 4].try_into()?,
+    );
+
+    if import_rva == 0 {
+        return Ok(vec![]);
+    }
+
+    let section_table = optional_header + size_of_optional_header;
+    let mut sections = Vec::with_capacity(number_of_sections);
+    for i in 0..number_of_sections {
+        let base = section_table + i * 40;
+        if base + 24 > data.len() {
+            break;
+        }
+
+        let virtual_size = u32::from_le_bytes(data[base + 8..base + 12].try_into()?);
+        let virtual_address = u32::from_le_bytes(data[base + 12..base + 16].try_into()?);
+        let pointer_to_raw_data = u32::from_le_bytes(data[base + 20..base + 24].try_into()?);
+        sections.push((virtual_address, virtual_size.max(1), pointer_to_raw_data));
+    }
+
+    let rva_to_offset = |rva: u32| -> Option<usize> {
+        sections.iter().find_map(|&(va, size, raw)| {
+            if rva >= va && rva < va + size {
+                Some((raw + (rva - va)) as usize)
+            } else {
+                None
+            }
+        })
+    };
+
+    let mut offset = match rva_to_offset(import_rva) {
+        Some(offset) => offset,
+        None => return Ok(vec![]),
+    };
+
+    let mut names = Vec::new();
+
+    while offset + 20 <= data.len() {
+        let original_first_thunk = u32::from_le_bytes(data[offset..offset + 4].try_into()?);
+        let name_rva = u32::from_le_bytes(data[offset + 12..offset + 16].try_into()?);
+        let first_thunk = u32::from_le_bytes(data[offset + 16..offset + 20].try_into()?);
+
+        if original_first_thunk == 0 && name_rva == 0 && first_thunk == 0 {
+            break;
+        }
+
+        if let Some(name_offset) = rva_to_offset(name_rva) {
+            if let Some(len) = data[name_offset..].iter().position(|&b| b == 0) {
+                if let Ok(name) = std::str::from_utf8(&data[name_offset..name_offset + len]) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+
+        offset += 20;
+    }
+
+    Ok(names)
+}
+
+/// Controls where the serialized packed resources data lives relative to the produced binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackedResourcesLoadMode {
+    /// Packed resources are serialized into the binary itself.
+    EmbeddedInBinary,
+    /// Packed resources are written to a standalone file next to the binary and memory
+    /// mapped by the runtime at startup.
+    BinaryRelativePathMemoryMapped { filename: String },
+    /// No packed resources data is produced.
+    None,
+}
+
+impl Default for PackedResourcesLoadMode {
+    fn default() -> Self {
+        PackedResourcesLoadMode::EmbeddedInBinary
+    }
+}
+
+/// Controls how the packed resources data blob is serialized by
+/// `EmbeddedPythonResources::write_blobs`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourcesSerializationOptions {
+    /// The packed resources format version to emit.
+    ///
+    /// Only version 1 (`write_embedded_resources_v1`) is currently supported.
+    pub format_version: u8,
+
+    /// Whether to compress the resources blob when it is at least `compression_threshold` bytes.
+    ///
+    /// `python_packed_resources::data::Resource` is an external type this crate doesn't vendor,
+    /// so there's no field on it to record a per-resource "this entry is compressed" flag the
+    /// way a fork of that crate could. Instead, `write_blobs` compresses the whole serialized
+    /// resources blob as one zlib stream and prefixes it with a flag byte of its own, which
+    /// still shrinks binaries dominated by large pure-Python source/bytecode payloads; the
+    /// runtime loader needs to check that flag byte before handing the rest to
+    /// `python_packed_resources`' reader.
+    pub compress: bool,
+
+    /// Minimum uncompressed blob size, in bytes, before compression is attempted.
+    pub compression_threshold: usize,
+
+    /// Whether to fold the module names list into the resources blob instead of writing it
+    /// to a separate newline-delimited stream.
+    pub fold_module_names_into_resources: bool,
+}
+
+impl Default for ResourcesSerializationOptions {
+    fn default() -> Self {
+        Self {
+            format_version: 1,
+            compress: false,
+            compression_threshold: 16384,
+            fold_module_names_into_resources: false,
+        }
+    }
+}
+
 /// Holds state necessary to link an extension module into libpython.
 #[derive(Debug, Clone, PartialEq)]
 pub struct ExtensionModuleBuildState {
@@ -56,6 +399,37 @@ pub struct ExtensionModuleBuildState {
 
     /// Dynamic libraries this extension module needs to link against.
     pub link_external_libraries: BTreeSet<String>,
+
+    /// Licensing metadata for this extension module, if any was declared.
+    pub licensed_component: Option<LicensedComponent>,
+}
+
+/// Compiles Python source to a marshalled code object.
+///
+/// Implemented by callers who already have a way to produce marshalled bytecode (e.g. an
+/// in-process embedding of the interpreter) and want to avoid `package()`'s out-of-process
+/// `BytecodeCompiler` round-trip.
+pub trait PythonBytecodeCompiler {
+    /// Compile `source` for `module_name` at the given optimization level, returning the
+    /// marshalled code object (no `.pyc` header).
+    fn compile(
+        &mut self,
+        source: &[u8],
+        module_name: &str,
+        optimize_level: BytecodeOptimizationLevel,
+    ) -> Result<Vec<u8>>;
+}
+
+/// Holds already-compiled `.pyc` data for a module, keyed by storage location and optimization
+/// level, mirroring the 3-way optimization level split used elsewhere in this module.
+#[derive(Debug, Clone, Default)]
+struct PrecompiledModuleBytecode {
+    in_memory: Option<Vec<u8>>,
+    in_memory_opt1: Option<Vec<u8>>,
+    in_memory_opt2: Option<Vec<u8>>,
+    relative_path: Option<(String, String, Vec<u8>)>,
+    relative_path_opt1: Option<(String, String, Vec<u8>)>,
+    relative_path_opt2: Option<(String, String, Vec<u8>)>,
 }
 
 /// Represents Python resources to embed in a binary.
@@ -66,6 +440,13 @@ pub struct ExtensionModuleBuildState {
 pub struct PrePackagedResources {
     collector: PythonResourceCollector,
     extension_module_states: BTreeMap<String, ExtensionModuleBuildState>,
+    licensed_components: LicensedComponents,
+    bytecode_invalidation_mode: BytecodeInvalidationMode,
+    packed_resources_load_mode: PackedResourcesLoadMode,
+    windows_runtime_dlls_mode: WindowsRuntimeDllsMode,
+    precompiled_bytecode: BTreeMap<String, PrecompiledModuleBytecode>,
+    extension_module_filter: ExtensionModuleFilter,
+    resources_serialization_options: ResourcesSerializationOptions,
 }
 
 impl PrePackagedResources {
@@ -73,7 +454,155 @@ impl PrePackagedResources {
         Self {
             collector: PythonResourceCollector::new(policy, cache_tag),
             extension_module_states: BTreeMap::new(),
+            licensed_components: LicensedComponents::default(),
+            bytecode_invalidation_mode: BytecodeInvalidationMode::default(),
+            packed_resources_load_mode: PackedResourcesLoadMode::default(),
+            windows_runtime_dlls_mode: WindowsRuntimeDllsMode::default(),
+            precompiled_bytecode: BTreeMap::new(),
+            extension_module_filter: ExtensionModuleFilter::default(),
+            resources_serialization_options: ResourcesSerializationOptions::default(),
+        }
+    }
+
+    /// Construct an instance whose resources prefer in-memory storage but fall back to
+    /// filesystem-relative storage under `prefix` when the resource collector can't hold a
+    /// given resource in memory (e.g. it's a package-relative extension module data blob).
+    ///
+    /// This is a thin convenience over `new()` for the common case of wanting the performance
+    /// of in-memory resources without giving up support for resource types that require a
+    /// location on disk. The collector's `PythonResourcesPolicy::PreferInMemoryFallbackFilesystemRelative`
+    /// handling governs which location plain `add_in_memory_*`/`add_relative_path_*` calls are
+    /// accepted under (unlike `InMemoryOnly`, an explicit relative-path add isn't rejected here);
+    /// actually storing the same resource in both locations is a property of the individual
+    /// resource entry, not of the policy, so use
+    /// `add_module_source_prefer_in_memory_fallback_filesystem_relative` (or the analogous
+    /// dual-add for other resource types) to get a resource that's embedded in memory and also
+    /// written to disk as a fallback.
+    pub fn new_prefer_in_memory_fallback_filesystem_relative(prefix: &str, cache_tag: &str) -> Self {
+        Self::new(
+            &PythonResourcesPolicy::PreferInMemoryFallbackFilesystemRelative(prefix.to_string()),
+            cache_tag,
+        )
+    }
+
+    /// Set the invalidation mode used for relative-path `.pyc` files produced by `package()`.
+    pub fn set_bytecode_invalidation_mode(&mut self, mode: BytecodeInvalidationMode) {
+        self.bytecode_invalidation_mode = mode;
+    }
+
+    /// Set the policy governing which builtin distribution extension modules are accepted by
+    /// `add_builtin_distribution_extension_module`.
+    pub fn set_extension_module_filter(&mut self, filter: ExtensionModuleFilter) {
+        self.extension_module_filter = filter;
+    }
+
+    /// Determine whether `module` is accepted under the current `ExtensionModuleFilter`.
+    fn extension_module_allowed(&self, module: &DistributionExtensionModule) -> bool {
+        match self.extension_module_filter {
+            ExtensionModuleFilter::All => true,
+            ExtensionModuleFilter::Minimal => module.required || module.builtin_default,
+            ExtensionModuleFilter::NoLibraries => module.links.is_empty(),
+            ExtensionModuleFilter::NoCopyleft => {
+                let links_non_system_library = module.links.iter().any(|link| {
+                    !link.framework && !SAFE_SYSTEM_LIBRARIES.contains(&link.name.as_str())
+                });
+
+                !links_non_system_library
+                    || !Self::licensed_component_for_extension_module(module)
+                        .map(|component| component.is_copyleft())
+                        .unwrap_or(false)
+            }
+        }
+    }
+
+    /// Set how the serialized packed resources data should be made available at run-time.
+    pub fn set_packed_resources_load_mode(&mut self, mode: PackedResourcesLoadMode) {
+        self.packed_resources_load_mode = mode;
+    }
+
+    /// Set whether Windows runtime DLLs should be bundled next to the produced binary.
+    pub fn set_windows_runtime_dlls_mode(&mut self, mode: WindowsRuntimeDllsMode) {
+        self.windows_runtime_dlls_mode = mode;
+    }
+
+    /// Set the options controlling how `EmbeddedPythonResources::write_blobs` serializes the
+    /// packed resources data blob.
+    pub fn set_resources_serialization_options(&mut self, options: ResourcesSerializationOptions) {
+        self.resources_serialization_options = options;
+    }
+
+    /// Determine which known Windows runtime DLLs are referenced by embedded shared libraries.
+    ///
+    /// Only extension modules and shared libraries installed relative to the binary are
+    /// inspected; in-memory extension modules and shared libraries are not. Libraries whose
+    /// import tables can't be parsed (e.g. non-Windows binaries) are skipped.
+    fn resolve_windows_runtime_dlls(&self) -> Result<BTreeSet<String>> {
+        let mut referenced = BTreeSet::new();
+
+        for resource in self.collector.resources.values() {
+            let data = if let Some((_, _, location)) =
+                &resource.relative_path_extension_module_shared_library
+            {
+                Some(location.resolve()?)
+            } else if let Some((_, location)) = &resource.relative_path_shared_library {
+                Some(location.resolve()?)
+            } else {
+                None
+            };
+
+            let data = match data {
+                Some(data) => data,
+                None => continue,
+            };
+
+            for imported in pe_import_dll_names(&data).unwrap_or_default() {
+                let imported = imported.to_lowercase();
+                if WINDOWS_RUNTIME_DLL_NAMES.contains(&imported.as_str()) {
+                    referenced.insert(imported);
+                }
+            }
         }
+
+        Ok(referenced)
+    }
+
+    /// Derive a `LicensedComponent` for a distribution extension module, if it declares any
+    /// licensing metadata.
+    ///
+    /// `DistributionExtensionModule` instances may declare a set of SPDX-ish license
+    /// identifiers, paths to license texts, and whether the code is public domain.
+    fn licensed_component_for_extension_module(
+        module: &DistributionExtensionModule,
+    ) -> Option<LicensedComponent> {
+        if module.license_public_domain == Some(true) {
+            return Some(LicensedComponent::new_public_domain(&module.module));
+        }
+
+        let licenses = module.licenses.as_ref()?;
+        if licenses.is_empty() {
+            return None;
+        }
+        let mut component = LicensedComponent::new_spdx(&module.module, &licenses.join(" OR "));
+
+        if let Some(license_paths) = &module.license_paths {
+            for path in license_paths {
+                component.add_license_text_path(path.clone());
+            }
+        }
+
+        Some(component)
+    }
+
+    /// Record licensing metadata for a distribution extension module, if it carries any,
+    /// folding it into the aggregated `LicensedComponents` so `package()` can later produce a
+    /// consolidated manifest, and returning it for storage on the module's build state.
+    fn record_extension_module_licensing(
+        &mut self,
+        module: &DistributionExtensionModule,
+    ) -> Option<LicensedComponent> {
+        let component = Self::licensed_component_for_extension_module(module)?;
+        self.licensed_components.add_component(component.clone());
+        Some(component)
     }
 
     /// Obtain `PythonModuleSource` in this instance.
@@ -93,6 +622,24 @@ impl PrePackagedResources {
         self.collector.get_in_memory_package_resources()
     }
 
+    /// Obtain package distribution (e.g. `METADATA`, `RECORD`) resource files in this instance.
+    pub fn get_in_memory_package_distribution_resources(
+        &self,
+    ) -> BTreeMap<String, BTreeMap<String, Vec<u8>>> {
+        self.collector.get_in_memory_package_distribution_resources()
+    }
+
+    /// Obtain package distribution resource files installed relative to the filesystem.
+    ///
+    /// Mirrors `get_in_memory_package_distribution_resources` for resources added via
+    /// `add_relative_path_package_distribution_resource`.
+    pub fn get_relative_path_package_distribution_resources(
+        &self,
+    ) -> BTreeMap<String, BTreeMap<String, PathBuf>> {
+        self.collector
+            .get_relative_path_package_distribution_resources()
+    }
+
     /// Add a source module to the collection of embedded source modules.
     pub fn add_in_memory_module_source(&mut self, module: &PythonModuleSource) -> Result<()> {
         self.collector.add_in_memory_python_module_source(module)
@@ -108,6 +655,27 @@ impl PrePackagedResources {
             .add_relative_path_python_module_source(module, prefix)
     }
 
+    /// Add module source stored both in memory and as a filesystem-relative fallback under
+    /// `prefix`, on the one resource entry.
+    ///
+    /// `add_in_memory_module_source` and `add_relative_path_module_source` each only populate
+    /// their own location field; calling both for the same module populates
+    /// `in_memory_module_source` and `relative_path_module_source` together on a single
+    /// `PrePackagedResource`, since they're independent fields on that type rather than
+    /// mutually exclusive ones. `write_blobs` then embeds the in-memory copy for fast loading
+    /// while `derive_extra_files` still writes the on-disk fallback a loader can use if
+    /// in-memory loading isn't available, matching the intent of
+    /// `PythonResourcesPolicy::PreferInMemoryFallbackFilesystemRelative`.
+    pub fn add_module_source_prefer_in_memory_fallback_filesystem_relative(
+        &mut self,
+        module: &PythonModuleSource,
+        prefix: &str,
+    ) -> Result<()> {
+        self.collector.add_in_memory_python_module_source(module)?;
+        self.collector
+            .add_relative_path_python_module_source(module, prefix)
+    }
+
     /// Add a bytecode module to the collection of embedded bytecode modules.
     pub fn add_in_memory_module_bytecode(
         &mut self,
@@ -127,9 +695,108 @@ impl PrePackagedResources {
             .add_relative_path_python_module_bytecode_from_source(module, prefix)
     }
 
-    /// Add resource data.
+    /// Compile `module` with `compiler` and produce a valid `.pyc` blob (header + marshalled
+    /// code object) ready to embed without `package()` needing to invoke a compiler itself.
+    fn precompile_pyc(
+        compiler: &mut dyn PythonBytecodeCompiler,
+        module: &PythonModuleSource,
+        optimize_level: BytecodeOptimizationLevel,
+    ) -> Result<Vec<u8>> {
+        let source = module.source.resolve()?;
+        let code = compiler.compile(&source, &module.name, optimize_level)?;
+        let header = pyc_header(
+            BytecodeInvalidationMode::UncheckedHash,
+            &module.cache_tag,
+            &source,
+            0,
+            source.len() as u32,
+        )?;
+
+        let mut data = Vec::with_capacity(header.len() + code.len());
+        data.extend_from_slice(&header);
+        data.extend_from_slice(&code);
+
+        Ok(data)
+    }
+
+    /// Add a module's already-compiled bytecode to be loaded from memory.
     ///
-    /// Resource data belongs to a Python package and has a name and bytes data.
+    /// Unlike `add_in_memory_module_bytecode`, which defers compilation of `source` until
+    /// `package()` runs, this compiles eagerly via the supplied `PythonBytecodeCompiler` and
+    /// stores the resulting `.pyc` bytes directly.
+    pub fn add_in_memory_module_bytecode_precompiled(
+        &mut self,
+        compiler: &mut dyn PythonBytecodeCompiler,
+        module: &PythonModuleSource,
+        optimize_level: BytecodeOptimizationLevel,
+    ) -> Result<()> {
+        let data = Self::precompile_pyc(compiler, module, optimize_level)?;
+        let entry = self.precompiled_bytecode.entry(module.name.clone()).or_default();
+
+        match optimize_level {
+            BytecodeOptimizationLevel::Zero => entry.in_memory = Some(data),
+            BytecodeOptimizationLevel::One => entry.in_memory_opt1 = Some(data),
+            BytecodeOptimizationLevel::Two => entry.in_memory_opt2 = Some(data),
+        }
+
+        // `package()` only folds precompiled_bytecode into modules it's already iterating
+        // via `collector.resources`. A module added solely through this method (no
+        // accompanying source/resource) would otherwise never be visited and its bytecode
+        // would be silently dropped.
+        self.collector
+            .resources
+            .entry(module.name.clone())
+            .or_insert_with(|| PrePackagedResource {
+                flavor: ResourceFlavor::Module,
+                name: module.name.clone(),
+                is_package: module.is_package,
+                ..PrePackagedResource::default()
+            });
+
+        Ok(())
+    }
+
+    /// Add a module's already-compiled bytecode to be loaded from the filesystem relative to
+    /// `prefix`. See `add_in_memory_module_bytecode_precompiled` for how compilation happens.
+    pub fn add_relative_path_module_bytecode_precompiled(
+        &mut self,
+        compiler: &mut dyn PythonBytecodeCompiler,
+        module: &PythonModuleSource,
+        optimize_level: BytecodeOptimizationLevel,
+        prefix: &str,
+    ) -> Result<()> {
+        let data = Self::precompile_pyc(compiler, module, optimize_level)?;
+        let entry = self.precompiled_bytecode.entry(module.name.clone()).or_default();
+        let location = (prefix.to_string(), module.cache_tag.clone(), data);
+
+        match optimize_level {
+            BytecodeOptimizationLevel::Zero => entry.relative_path = Some(location),
+            BytecodeOptimizationLevel::One => entry.relative_path_opt1 = Some(location),
+            BytecodeOptimizationLevel::Two => entry.relative_path_opt2 = Some(location),
+        }
+
+        // See the comment in `add_in_memory_module_bytecode_precompiled`: without this,
+        // a module added solely through this method is never iterated by `package()`.
+        self.collector
+            .resources
+            .entry(module.name.clone())
+            .or_insert_with(|| PrePackagedResource {
+                flavor: ResourceFlavor::Module,
+                name: module.name.clone(),
+                is_package: module.is_package,
+                ..PrePackagedResource::default()
+            });
+
+        Ok(())
+    }
+
+    /// Add non-code package data (e.g. `*.json`, `*.dat`, templates) for in-memory loading.
+    ///
+    /// This is how `importlib.resources`/`pkg_resources` data files get embedded: the
+    /// collector stores `resource.data` keyed by `(resource.leaf_package, resource.relative_name)`
+    /// under `ResourceFlavor::Resource`, and `package()` serializes that entry alongside module
+    /// and extension resources so the embedded importer can answer `open_binary`/`files()`
+    /// lookups without touching the filesystem.
     pub fn add_in_memory_package_resource(
         &mut self,
         resource: &PythonPackageResource,
@@ -138,7 +805,12 @@ impl PrePackagedResources {
             .add_in_memory_python_package_resource(resource)
     }
 
-    /// Add resource data to be loaded from the filesystem.
+    /// Add non-code package data to be loaded from the filesystem relative to the resources.
+    ///
+    /// `derive_extra_files` lays the resource out at `prefix/<package path>/<resource name>`,
+    /// mirroring how `add_relative_path_extension_module` computes its path, so the embedded
+    /// importer's filesystem-relative lookups find it at the same place the on-disk package
+    /// layout would put it.
     pub fn add_relative_path_package_resource(
         &mut self,
         prefix: &str,
@@ -172,11 +844,19 @@ impl PrePackagedResources {
     /// `libpython` and the extension module will be registered in the list of
     /// the set of extension modules available for import with Python's *builtin*
     /// importer.
+    ///
+    /// The module is silently skipped if it is rejected by the current
+    /// `ExtensionModuleFilter` (see `set_extension_module_filter`).
     pub fn add_builtin_distribution_extension_module(
         &mut self,
         module: &DistributionExtensionModule,
     ) -> Result<()> {
-        // No policy check because distribution extension modules are special.
+        // No other policy check because distribution extension modules are special.
+        if !self.extension_module_allowed(module) {
+            return Ok(());
+        }
+
+        let licensed_component = self.record_extension_module_licensing(module);
 
         self.extension_module_states.insert(
             module.module.clone(),
@@ -226,6 +906,7 @@ impl PrePackagedResources {
                     },
                 )),
                 link_external_libraries: BTreeSet::new(),
+                licensed_component,
             },
         );
 
@@ -246,6 +927,8 @@ impl PrePackagedResources {
         self.collector
             .add_in_memory_python_extension_module_shared_library(&module.module, false, &data)?;
 
+        self.record_extension_module_licensing(module);
+
         for link in &module.links {
             if let Some(shared_library) = &link.dynamic_path {
                 // Add a resource holding the shared library data.
@@ -314,8 +997,8 @@ impl PrePackagedResources {
             // Install dynamic library dependencies next to extension module.
             //
             // On Windows, this should "just work" since the opening DLL's directory
-            // is searched for dependencies.
-            // TODO this logic likely needs to be expanded.
+            // is searched for dependencies. `windows_runtime_dlls_mode` separately handles
+            // the VC runtime DLLs that aren't recorded as explicit link dependencies.
             if let Some(shared_library) = &link.dynamic_path {
                 let file_name = shared_library
                     .file_name()
@@ -340,6 +1023,8 @@ impl PrePackagedResources {
             }
         }
 
+        self.record_extension_module_licensing(module);
+
         Ok(())
     }
 
@@ -372,6 +1057,7 @@ impl PrePackagedResources {
                 link_static_libraries: BTreeSet::new(),
                 link_dynamic_libraries: BTreeSet::new(),
                 link_external_libraries: BTreeSet::from_iter(module.libraries.iter().cloned()),
+                licensed_component: None,
             },
         );
 
@@ -420,6 +1106,247 @@ impl PrePackagedResources {
         Ok(())
     }
 
+    /// Remove extension modules whose linked libraries carry a copyleft license.
+    ///
+    /// System libraries on `SAFE_SYSTEM_LIBRARIES` are never considered copyleft, since they
+    /// are assumed to already ship with the target operating system. Everything else is
+    /// checked against the licensing metadata recorded by `record_extension_module_licensing`;
+    /// an extension module is dropped from both `extension_module_states` and
+    /// `collector.resources` as soon as one of its linked libraries is copyleft-licensed.
+    pub fn filter_extension_modules_by_license(&mut self, logger: &slog::Logger) -> Result<()> {
+        let copyleft_modules: Vec<String> = self
+            .extension_module_states
+            .iter()
+            .filter(|(name, state)| {
+                let links_non_system_library = state
+                    .link_system_libraries
+                    .iter()
+                    .chain(&state.link_static_libraries)
+                    .chain(&state.link_dynamic_libraries)
+                    .any(|library| !SAFE_SYSTEM_LIBRARIES.contains(&library.as_str()));
+
+                links_non_system_library
+                    && self
+                        .licensed_components
+                        .get(name)
+                        .map(LicensedComponent::is_copyleft)
+                        .unwrap_or(false)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        for name in copyleft_modules {
+            warn!(
+                logger,
+                "removing extension module {} because it links copyleft-licensed code", name
+            );
+            self.extension_module_states.remove(&name);
+            self.collector.resources.remove(&name);
+        }
+
+        Ok(())
+    }
+
+    /// Compile a relative-path module's bytecode, prefixed with a `.pyc` header matching
+    /// `self.bytecode_invalidation_mode`.
+    fn compile_relative_path_pyc(
+        &self,
+        compiler: &mut BytecodeCompiler,
+        location: &DataLocation,
+        name: &str,
+        optimize_level: BytecodeOptimizationLevel,
+        cache_tag: &str,
+    ) -> Result<Vec<u8>> {
+        let source = location.resolve()?;
+
+        let (source_mtime, source_size) = match location {
+            DataLocation::Path(path) => {
+                let metadata = std::fs::metadata(path)?;
+                let mtime = metadata
+                    .modified()?
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs() as u32)
+                    .unwrap_or(0);
+
+                (mtime, metadata.len() as u32)
+            }
+            DataLocation::Memory(_) => (0, source.len() as u32),
+        };
+
+        let code = compiler.compile(&source, name, optimize_level, CompileMode::Bytecode)?;
+        let header = pyc_header(
+            self.bytecode_invalidation_mode,
+            cache_tag,
+            &source,
+            source_mtime,
+            source_size,
+        )?;
+
+        let mut data = Vec::with_capacity(header.len() + code.len());
+        data.extend_from_slice(&header);
+        data.extend_from_slice(&code);
+
+        Ok(data)
+    }
+
+    /// Compile all bytecode-bearing fields of a single resource entry.
+    ///
+    /// Returns the `Resource` populated with its compiled bytecode plus any relative-path
+    /// `.pyc` files that need to be written alongside the binary. This is the unit of work
+    /// distributed across the worker pool in `package()`.
+    fn compile_resource_entry(
+        &self,
+        compiler: &mut BytecodeCompiler,
+        name: &str,
+        module: &PrePackagedResource,
+    ) -> Result<(Resource<'static, u8>, Vec<(PathBuf, FileContent)>)> {
+        let mut entry = Resource::try_from(module)?;
+        let mut extra_files = Vec::new();
+
+        if let Some(location) = &module.in_memory_bytecode_source {
+            entry.in_memory_bytecode = Some(Cow::Owned(compiler.compile(
+                &location.resolve()?,
+                name,
+                BytecodeOptimizationLevel::Zero,
+                CompileMode::Bytecode,
+            )?));
+        }
+
+        if let Some(location) = &module.in_memory_bytecode_opt1_source {
+            entry.in_memory_bytecode_opt1 = Some(Cow::Owned(compiler.compile(
+                &location.resolve()?,
+                name,
+                BytecodeOptimizationLevel::One,
+                CompileMode::Bytecode,
+            )?));
+        }
+
+        if let Some(location) = &module.in_memory_bytecode_opt2_source {
+            entry.in_memory_bytecode_opt2 = Some(Cow::Owned(compiler.compile(
+                &location.resolve()?,
+                name,
+                BytecodeOptimizationLevel::Two,
+                CompileMode::Bytecode,
+            )?));
+        }
+
+        if let Some((prefix, cache_tag, location)) = &module.relative_path_bytecode_source {
+            let bytecode_module = PythonModuleBytecodeFromSource {
+                name: name.to_string(),
+                source: DataLocation::Memory(vec![]),
+                optimize_level: BytecodeOptimizationLevel::Zero,
+                is_package: entry.is_package,
+                cache_tag: cache_tag.clone(),
+            };
+
+            let path = bytecode_module.resolve_path(prefix);
+            let data =
+                self.compile_relative_path_pyc(compiler, location, name, BytecodeOptimizationLevel::Zero, cache_tag)?;
+            extra_files.push((path.clone(), FileContent { data, executable: false }));
+
+            entry.relative_path_module_bytecode = Some(Cow::Owned(path));
+        }
+
+        if let Some((prefix, cache_tag, location)) = &module.relative_path_bytecode_opt1_source {
+            let bytecode_module = PythonModuleBytecodeFromSource {
+                name: name.to_string(),
+                source: DataLocation::Memory(vec![]),
+                optimize_level: BytecodeOptimizationLevel::One,
+                is_package: entry.is_package,
+                cache_tag: cache_tag.clone(),
+            };
+
+            let path = bytecode_module.resolve_path(prefix);
+            let data =
+                self.compile_relative_path_pyc(compiler, location, name, BytecodeOptimizationLevel::One, cache_tag)?;
+            extra_files.push((path.clone(), FileContent { data, executable: false }));
+
+            entry.relative_path_module_bytecode_opt1 = Some(Cow::Owned(path));
+        }
+
+        if let Some((prefix, cache_tag, location)) = &module.relative_path_bytecode_opt2_source {
+            let bytecode_module = PythonModuleBytecodeFromSource {
+                name: name.to_string(),
+                source: DataLocation::Memory(vec![]),
+                optimize_level: BytecodeOptimizationLevel::Two,
+                is_package: entry.is_package,
+                cache_tag: cache_tag.clone(),
+            };
+
+            let path = bytecode_module.resolve_path(prefix);
+            let data =
+                self.compile_relative_path_pyc(compiler, location, name, BytecodeOptimizationLevel::Two, cache_tag)?;
+            extra_files.push((path.clone(), FileContent { data, executable: false }));
+
+            entry.relative_path_module_bytecode_opt2 = Some(Cow::Owned(path));
+        }
+
+        if let Some(precompiled) = self.precompiled_bytecode.get(name) {
+            if let Some(data) = &precompiled.in_memory {
+                entry.in_memory_bytecode = Some(Cow::Owned(data.clone()));
+            }
+            if let Some(data) = &precompiled.in_memory_opt1 {
+                entry.in_memory_bytecode_opt1 = Some(Cow::Owned(data.clone()));
+            }
+            if let Some(data) = &precompiled.in_memory_opt2 {
+                entry.in_memory_bytecode_opt2 = Some(Cow::Owned(data.clone()));
+            }
+
+            if let Some((prefix, cache_tag, data)) = &precompiled.relative_path {
+                let path = PythonModuleBytecodeFromSource {
+                    name: name.to_string(),
+                    source: DataLocation::Memory(vec![]),
+                    optimize_level: BytecodeOptimizationLevel::Zero,
+                    is_package: entry.is_package,
+                    cache_tag: cache_tag.clone(),
+                }
+                .resolve_path(prefix);
+
+                extra_files.push((
+                    path.clone(),
+                    FileContent { data: data.clone(), executable: false },
+                ));
+                entry.relative_path_module_bytecode = Some(Cow::Owned(path));
+            }
+
+            if let Some((prefix, cache_tag, data)) = &precompiled.relative_path_opt1 {
+                let path = PythonModuleBytecodeFromSource {
+                    name: name.to_string(),
+                    source: DataLocation::Memory(vec![]),
+                    optimize_level: BytecodeOptimizationLevel::One,
+                    is_package: entry.is_package,
+                    cache_tag: cache_tag.clone(),
+                }
+                .resolve_path(prefix);
+
+                extra_files.push((
+                    path.clone(),
+                    FileContent { data: data.clone(), executable: false },
+                ));
+                entry.relative_path_module_bytecode_opt1 = Some(Cow::Owned(path));
+            }
+
+            if let Some((prefix, cache_tag, data)) = &precompiled.relative_path_opt2 {
+                let path = PythonModuleBytecodeFromSource {
+                    name: name.to_string(),
+                    source: DataLocation::Memory(vec![]),
+                    optimize_level: BytecodeOptimizationLevel::Two,
+                    is_package: entry.is_package,
+                    cache_tag: cache_tag.clone(),
+                }
+                .resolve_path(prefix);
+
+                extra_files.push((
+                    path.clone(),
+                    FileContent { data: data.clone(), executable: false },
+                ));
+                entry.relative_path_module_bytecode_opt2 = Some(Cow::Owned(path));
+            }
+        }
+
+        Ok((entry, extra_files))
+    }
+
     fn derive_extra_files(&self) -> Result<FileManifest> {
         let mut m = FileManifest::default();
 
@@ -469,124 +1396,48 @@ impl PrePackagedResources {
         let mut resources = BTreeMap::new();
         let mut extra_files = self.derive_extra_files()?;
 
-        let mut compiler = BytecodeCompiler::new(&python_exe)?;
-        {
-            for (name, module) in &input_resources {
-                let mut entry = Resource::try_from(module)?;
-
-                if let Some(location) = &module.in_memory_bytecode_source {
-                    entry.in_memory_bytecode = Some(Cow::Owned(compiler.compile(
-                        &location.resolve()?,
-                        &name,
-                        BytecodeOptimizationLevel::Zero,
-                        CompileMode::Bytecode,
-                    )?));
-                }
-
-                if let Some(location) = &module.in_memory_bytecode_opt1_source {
-                    entry.in_memory_bytecode_opt1 = Some(Cow::Owned(compiler.compile(
-                        &location.resolve()?,
-                        &name,
-                        BytecodeOptimizationLevel::One,
-                        CompileMode::Bytecode,
-                    )?));
-                }
-
-                if let Some(location) = &module.in_memory_bytecode_opt2_source {
-                    entry.in_memory_bytecode_opt2 = Some(Cow::Owned(compiler.compile(
-                        &location.resolve()?,
-                        &name,
-                        BytecodeOptimizationLevel::Two,
-                        CompileMode::Bytecode,
-                    )?));
-                }
-
-                if let Some((prefix, cache_tag, location)) = &module.relative_path_bytecode_source {
-                    let module = PythonModuleBytecodeFromSource {
-                        name: name.clone(),
-                        source: DataLocation::Memory(vec![]),
-                        optimize_level: BytecodeOptimizationLevel::Zero,
-                        is_package: entry.is_package,
-                        cache_tag: cache_tag.clone(),
-                    };
-
-                    let path = module.resolve_path(prefix);
-
-                    extra_files.add_file(
-                        &path,
-                        &FileContent {
-                            data: compiler.compile(
-                                &location.resolve()?,
-                                &name,
-                                BytecodeOptimizationLevel::Zero,
-                                CompileMode::PycUncheckedHash,
-                            )?,
-                            executable: false,
-                        },
-                    )?;
-
-                    entry.relative_path_module_bytecode = Some(Cow::Owned(path));
-                }
-
-                if let Some((prefix, cache_tag, location)) =
-                    &module.relative_path_bytecode_opt1_source
-                {
-                    let module = PythonModuleBytecodeFromSource {
-                        name: name.clone(),
-                        source: DataLocation::Memory(vec![]),
-                        optimize_level: BytecodeOptimizationLevel::One,
-                        is_package: entry.is_package,
-                        cache_tag: cache_tag.clone(),
-                    };
-
-                    let path = module.resolve_path(prefix);
-
-                    extra_files.add_file(
-                        &path,
-                        &FileContent {
-                            data: compiler.compile(
-                                &location.resolve()?,
-                                &name,
-                                BytecodeOptimizationLevel::One,
-                                CompileMode::PycUncheckedHash,
-                            )?,
-                            executable: false,
-                        },
-                    )?;
-
-                    entry.relative_path_module_bytecode_opt1 = Some(Cow::Owned(path));
-                }
+        // Each compile job round-trips through an out-of-process Python interpreter, so
+        // spread the work across a pool of compilers rather than blocking on one at a time.
+        let worker_count = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(input_resources.len().max(1));
+
+        let items: Vec<(&String, &PrePackagedResource)> = input_resources.iter().collect();
+        let chunk_size = (items.len() + worker_count - 1) / worker_count.max(1);
+        let chunk_size = chunk_size.max(1);
+
+        let chunk_results: Vec<Result<Vec<(String, Resource<'static, u8>, Vec<(PathBuf, FileContent)>)>>> =
+            std::thread::scope(|scope| {
+                items
+                    .chunks(chunk_size)
+                    .map(|chunk| {
+                        scope.spawn(move || -> Result<Vec<(String, Resource<'static, u8>, Vec<(PathBuf, FileContent)>)>> {
+                            let mut compiler = BytecodeCompiler::new(python_exe)?;
+
+                            chunk
+                                .iter()
+                                .map(|(name, module)| {
+                                    let (entry, files) =
+                                        self.compile_resource_entry(&mut compiler, name, module)?;
+                                    Ok(((*name).clone(), entry, files))
+                                })
+                                .collect()
+                        })
+                    })
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .map(|handle| handle.join().expect("bytecode compiler worker panicked"))
+                    .collect()
+            });
 
-                if let Some((prefix, cache_tag, location)) =
-                    &module.relative_path_bytecode_opt2_source
-                {
-                    let module = PythonModuleBytecodeFromSource {
-                        name: name.clone(),
-                        source: DataLocation::Memory(vec![]),
-                        optimize_level: BytecodeOptimizationLevel::Two,
-                        is_package: entry.is_package,
-                        cache_tag: cache_tag.clone(),
-                    };
-
-                    let path = module.resolve_path(prefix);
-
-                    extra_files.add_file(
-                        &path,
-                        &FileContent {
-                            data: compiler.compile(
-                                &location.resolve()?,
-                                &name,
-                                BytecodeOptimizationLevel::Two,
-                                CompileMode::PycUncheckedHash,
-                            )?,
-                            executable: false,
-                        },
-                    )?;
-
-                    entry.relative_path_module_bytecode_opt1 = Some(Cow::Owned(path));
+        for chunk_result in chunk_results {
+            for (name, entry, files) in chunk_result? {
+                for (path, content) in files {
+                    extra_files.add_file(&path, &content)?;
                 }
 
-                resources.insert(name.clone(), entry);
+                resources.insert(name, entry);
             }
         }
 
@@ -613,10 +1464,70 @@ impl PrePackagedResources {
             }
         }
 
+        if let PackedResourcesLoadMode::BinaryRelativePathMemoryMapped { filename } =
+            &self.packed_resources_load_mode
+        {
+            let mut packed = Vec::new();
+            write_resources_blob(
+                &resources.values().cloned().collect::<Vec<Resource<u8>>>(),
+                &self.resources_serialization_options,
+                &mut packed,
+            )?;
+
+            extra_files.add_file(
+                &PathBuf::from(filename),
+                &FileContent {
+                    data: packed,
+                    executable: false,
+                },
+            )?;
+        }
+
+        if self.windows_runtime_dlls_mode != WindowsRuntimeDllsMode::Never {
+            let needed = if self.windows_runtime_dlls_mode == WindowsRuntimeDllsMode::Always {
+                WINDOWS_RUNTIME_DLL_NAMES
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect()
+            } else {
+                self.resolve_windows_runtime_dlls()?
+            };
+
+            for name in needed {
+                let found = python_exe
+                    .parent()
+                    .map(|dir| dir.join(&name))
+                    .and_then(|path| std::fs::read(&path).ok());
+
+                match found {
+                    Some(data) => {
+                        extra_files.add_file(
+                            &PathBuf::from(&name),
+                            &FileContent {
+                                data,
+                                executable: false,
+                            },
+                        )?;
+                    }
+                    None => {
+                        warn!(
+                            logger,
+                            "unable to locate Windows runtime DLL {} next to the Python interpreter; \
+                             the produced binary may not run on a machine without it installed",
+                            name
+                        );
+                    }
+                }
+            }
+        }
+
         Ok(EmbeddedPythonResources {
             resources,
             extra_files,
             extension_modules: self.extension_module_states.clone(),
+            licensing: self.licensed_components.clone(),
+            packed_resources_load_mode: self.packed_resources_load_mode.clone(),
+            resources_serialization_options: self.resources_serialization_options.clone(),
         })
     }
 }
@@ -630,6 +1541,9 @@ pub struct LibpythonLinkingInfo {
     pub link_frameworks: BTreeSet<String>,
     pub link_system_libraries: BTreeSet<String>,
     pub link_libraries_external: BTreeSet<String>,
+
+    /// Licensing metadata for the extension modules being linked into libpython.
+    pub licensed_components: LicensedComponents,
 }
 
 /// Represents Python resources to embed in a binary.
@@ -643,26 +1557,99 @@ pub struct EmbeddedPythonResources<'a> {
 
     /// Holds state needed for adding extension modules to libpython.
     extension_modules: BTreeMap<String, ExtensionModuleBuildState>,
+
+    /// Aggregated licensing metadata for all embedded resources.
+    licensing: LicensedComponents,
+
+    /// Where the serialized packed resources data should live.
+    packed_resources_load_mode: PackedResourcesLoadMode,
+
+    /// Options controlling how the packed resources data blob is serialized.
+    resources_serialization_options: ResourcesSerializationOptions,
+}
+
+/// Serialize `resources` per `options` and write the result to `out`.
+///
+/// This is the single place that applies `options.compress`/`compression_threshold`, so every
+/// writer of a packed resources blob -- `EmbeddedPythonResources::write_blobs` and
+/// `PrePackagedResources::package`'s `BinaryRelativePathMemoryMapped` handling alike -- produces
+/// the same framing: a flag byte (`1` for zlib-compressed, `0` for raw) followed by the
+/// (possibly compressed) `write_embedded_resources_v1` output. A runtime loader must read that
+/// flag byte before handing the rest to `python_packed_resources`' reader, regardless of which
+/// `PackedResourcesLoadMode` produced the blob.
+fn write_resources_blob(
+    resources: &[Resource<u8>],
+    options: &ResourcesSerializationOptions,
+    out: &mut impl Write,
+) -> Result<()> {
+    if options.format_version != 1 {
+        return Err(anyhow!(
+            "packed resources format version {} is not supported; only version 1 is implemented",
+            options.format_version
+        ));
+    }
+
+    let mut uncompressed = Vec::new();
+    write_embedded_resources_v1(resources, &mut uncompressed, None)?;
+
+    if options.compress && uncompressed.len() >= options.compression_threshold {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&uncompressed)?;
+        let compressed = encoder.finish()?;
+
+        out.write_all(&[1u8])?;
+        out.write_all(&compressed)?;
+    } else {
+        out.write_all(&[0u8])?;
+        out.write_all(&uncompressed)?;
+    }
+
+    Ok(())
 }
 
 impl<'a> EmbeddedPythonResources<'a> {
     /// Write entities defining resources.
+    ///
+    /// When the packed resources load mode is `BinaryRelativePathMemoryMapped`, the
+    /// resources blob was already written to `extra_install_files()` by `package()`, and
+    /// when it is `None` no resources blob is produced at all; in both cases `resources`
+    /// is left untouched.
+    ///
+    /// When `resources_serialization_options.fold_module_names_into_resources` is set, the
+    /// `module_names` stream is left untouched and module names are only discoverable by
+    /// reading the resources blob itself.
+    ///
+    /// The blob written to `resources` always starts with a single flag byte (`1` for
+    /// zlib-compressed, `0` for raw), followed by the (possibly compressed) blob. It is
+    /// compressed only when `resources_serialization_options.compress` is set and the
+    /// serialized resources blob is at least `compression_threshold` bytes. The runtime loader
+    /// must read that flag byte before handing the remainder to `python_packed_resources`'
+    /// reader.
     pub fn write_blobs<W: Write>(&self, module_names: &mut W, resources: &mut W) -> Result<()> {
-        for name in self.resources.keys() {
-            module_names
-                .write_all(name.as_bytes())
-                .expect("failed to write");
-            module_names.write_all(b"\n").expect("failed to write");
+        if !self
+            .resources_serialization_options
+            .fold_module_names_into_resources
+        {
+            for name in self.resources.keys() {
+                module_names
+                    .write_all(name.as_bytes())
+                    .expect("failed to write");
+                module_names.write_all(b"\n").expect("failed to write");
+            }
+        }
+
+        if self.packed_resources_load_mode != PackedResourcesLoadMode::EmbeddedInBinary {
+            return Ok(());
         }
 
-        write_embedded_resources_v1(
+        write_resources_blob(
             &self
                 .resources
                 .values()
                 .cloned()
                 .collect::<Vec<Resource<'a, u8>>>(),
+            &self.resources_serialization_options,
             resources,
-            None,
         )
     }
 
@@ -691,6 +1678,27 @@ impl<'a> EmbeddedPythonResources<'a> {
         Ok(res)
     }
 
+    /// Obtain the aggregated licensing metadata for all embedded resources.
+    pub fn licensing(&self) -> &LicensedComponents {
+        &self.licensing
+    }
+
+    /// Render an aggregated license manifest for the embedded resources.
+    ///
+    /// The returned string begins with the overall SPDX expression for the
+    /// embedded components followed by the full text of each distinct license,
+    /// suitable for writing to a `THIRD-PARTY-LICENSES` file next to the
+    /// produced binary.
+    pub fn license_manifest_text(&self) -> Result<String> {
+        let mut out = format!("SPDX-License-Identifier: {}\n\n", self.licensing.spdx_expression());
+
+        for (name, text) in self.licensing.license_texts()? {
+            out.push_str(&format!("-- {} --\n\n{}\n\n", name, text));
+        }
+
+        Ok(out)
+    }
+
     /// Resolve state needed to link a libpython.
     pub fn resolve_libpython_linking_info(
         &self,
@@ -701,6 +1709,7 @@ impl<'a> EmbeddedPythonResources<'a> {
         let mut link_frameworks = BTreeSet::new();
         let mut link_system_libraries = BTreeSet::new();
         let mut link_libraries_external = BTreeSet::new();
+        let mut licensed_components = LicensedComponents::default();
 
         warn!(
             logger,
@@ -743,6 +1752,10 @@ impl<'a> EmbeddedPythonResources<'a> {
                 warn!(logger, "dynamic library {} required by {}", library, name);
                 link_libraries_external.insert(library.clone());
             }
+
+            if let Some(component) = &state.licensed_component {
+                licensed_components.add_component(component.clone());
+            }
         }
 
         Ok(LibpythonLinkingInfo {
@@ -751,6 +1764,7 @@ impl<'a> EmbeddedPythonResources<'a> {
             link_frameworks,
             link_system_libraries,
             link_libraries_external,
+            licensed_components,
         })
     }
 }
@@ -761,6 +1775,38 @@ mod tests {
 
     const DEFAULT_CACHE_TAG: &str = "cpython-37";
 
+    #[test]
+    fn test_source_hash_matches_cpython_keyed_hash() -> Result<()> {
+        // Real output of CPython 3.8's `importlib.util.source_hash`, which is backed by
+        // `_imp.source_hash(_RAW_MAGIC_NUMBER, source_bytes)` -> `_Py_KeyedHash`. CPython 3.8
+        // builds with SipHash-2-4, and `_RAW_MAGIC_NUMBER` for cpython-38 is 168_627_541
+        // (`int.from_bytes(importlib.util.MAGIC_NUMBER[:4], "little")`), matching
+        // `pyc_magic_number("cpython-38")` below.
+        let magic = pyc_magic_number("cpython-38")?;
+        assert_eq!(u32::from_le_bytes(magic), 168_627_541);
+
+        assert_eq!(
+            source_hash(&magic, b"hello world"),
+            [0x5b, 0xee, 0xe8, 0xf4, 0xf1, 0xa8, 0xe5, 0x94]
+        );
+        assert_eq!(
+            source_hash(&magic, b"hello world!"),
+            [0x5f, 0x7c, 0x83, 0x7e, 0x4b, 0x84, 0x1a, 0xac]
+        );
+        assert_eq!(
+            source_hash(&magic, b""),
+            [0x81, 0xe4, 0x88, 0x1b, 0x36, 0x43, 0xe8, 0xe9]
+        );
+
+        let other_magic = pyc_magic_number(DEFAULT_CACHE_TAG)?;
+        assert_ne!(
+            source_hash(&magic, b"hello world"),
+            source_hash(&other_magic, b"hello world")
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_add_relative_path_source_module() -> Result<()> {
         let mut r = PrePackagedResources::new(
@@ -803,6 +1849,265 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_add_in_memory_package_resource() -> Result<()> {
+        let mut r = PrePackagedResources::new(&PythonResourcesPolicy::InMemoryOnly, DEFAULT_CACHE_TAG);
+
+        r.add_in_memory_package_resource(&PythonPackageResource {
+            leaf_package: "foo".to_string(),
+            relative_name: "resource.txt".to_string(),
+            data: DataLocation::Memory(vec![42]),
+            is_stdlib: false,
+            is_test: false,
+        })?;
+
+        assert_eq!(
+            r.get_in_memory_package_resources().get("foo"),
+            Some(&BTreeMap::from_iter([(
+                "resource.txt".to_string(),
+                vec![42]
+            )]))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_relative_path_package_resource() -> Result<()> {
+        let mut r = PrePackagedResources::new(
+            &PythonResourcesPolicy::FilesystemRelativeOnly("".to_string()),
+            DEFAULT_CACHE_TAG,
+        );
+
+        // `derive_extra_files` must lay the resource out at `prefix/<package path>/<resource
+        // name>`, mirroring how `add_relative_path_extension_module` computes its path, so
+        // the embedded importer's filesystem-relative lookups find it at the same place the
+        // on-disk package layout would put it.
+        r.add_relative_path_package_resource(
+            "prefix",
+            &PythonPackageResource {
+                leaf_package: "foo.bar".to_string(),
+                relative_name: "resource.txt".to_string(),
+                data: DataLocation::Memory(vec![42]),
+                is_stdlib: false,
+                is_test: false,
+            },
+        )?;
+
+        let m = r.derive_extra_files()?;
+        let entries = m.entries().collect::<Vec<(&PathBuf, &FileContent)>>();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].0,
+            &PathBuf::from("prefix/foo/bar/resource.txt")
+        );
+        assert_eq!(
+            entries[0].1,
+            &FileContent {
+                data: vec![42],
+                executable: false
+            }
+        );
+
+        Ok(())
+    }
+
+    struct StubBytecodeCompiler;
+
+    impl PythonBytecodeCompiler for StubBytecodeCompiler {
+        fn compile(
+            &mut self,
+            source: &[u8],
+            _module_name: &str,
+            _optimize_level: BytecodeOptimizationLevel,
+        ) -> Result<Vec<u8>> {
+            Ok(source.to_vec())
+        }
+    }
+
+    #[test]
+    fn test_add_in_memory_module_bytecode_precompiled_without_source() -> Result<()> {
+        let mut r = PrePackagedResources::new(&PythonResourcesPolicy::InMemoryOnly, DEFAULT_CACHE_TAG);
+        let mut compiler = StubBytecodeCompiler;
+
+        r.add_in_memory_module_bytecode_precompiled(
+            &mut compiler,
+            &PythonModuleSource {
+                name: "foo".to_string(),
+                source: DataLocation::Memory(vec![42]),
+                is_package: false,
+                cache_tag: DEFAULT_CACHE_TAG.to_string(),
+            },
+            BytecodeOptimizationLevel::Zero,
+        )?;
+
+        // A module added solely through the precompiled-bytecode API must still be visited
+        // by `package()`, which only iterates `collector.resources`.
+        assert!(r.collector.resources.contains_key("foo"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_prefer_in_memory_fallback_filesystem_relative() -> Result<()> {
+        let mut r = PrePackagedResources::new_prefer_in_memory_fallback_filesystem_relative(
+            "prefix",
+            DEFAULT_CACHE_TAG,
+        );
+
+        assert!(r.extension_module_states.is_empty());
+
+        // A resource type that requires a filesystem location (here, an explicit
+        // relative-path add) must still be accepted under this policy's fallback, not
+        // rejected the way it would be under `PythonResourcesPolicy::InMemoryOnly`.
+        r.add_relative_path_module_source(
+            &PythonModuleSource {
+                name: "foo".to_string(),
+                source: DataLocation::Memory(vec![42]),
+                is_package: false,
+                cache_tag: DEFAULT_CACHE_TAG.to_string(),
+            },
+            "prefix",
+        )?;
+
+        let m = r.derive_extra_files()?;
+        let entries = m.entries().collect::<Vec<(&PathBuf, &FileContent)>>();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, &PathBuf::from("prefix/foo.py"));
+
+        // The dual-storage add must populate both location fields on the one resource entry,
+        // not just one or the other.
+        r.add_module_source_prefer_in_memory_fallback_filesystem_relative(
+            &PythonModuleSource {
+                name: "bar".to_string(),
+                source: DataLocation::Memory(vec![43]),
+                is_package: false,
+                cache_tag: DEFAULT_CACHE_TAG.to_string(),
+            },
+            "prefix",
+        )?;
+
+        assert_eq!(
+            r.get_in_memory_module_sources().get("bar").map(|m| &m.source),
+            Some(&DataLocation::Memory(vec![43]))
+        );
+
+        let m = r.derive_extra_files()?;
+        let entries = m.entries().collect::<Vec<(&PathBuf, &FileContent)>>();
+        assert_eq!(entries.len(), 2);
+        assert!(entries
+            .iter()
+            .any(|(path, _)| *path == &PathBuf::from("prefix/bar.py")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_blobs_rejects_unsupported_format_version() {
+        let embedded = EmbeddedPythonResources {
+            resources_serialization_options: ResourcesSerializationOptions {
+                format_version: 2,
+                ..ResourcesSerializationOptions::default()
+            },
+            ..EmbeddedPythonResources::default()
+        };
+
+        let mut module_names = Vec::new();
+        let mut resources = Vec::new();
+        assert!(embedded
+            .write_blobs(&mut module_names, &mut resources)
+            .is_err());
+    }
+
+    #[test]
+    fn test_write_blobs_folds_module_names_into_resources() -> Result<()> {
+        let embedded = EmbeddedPythonResources {
+            resources_serialization_options: ResourcesSerializationOptions {
+                fold_module_names_into_resources: true,
+                ..ResourcesSerializationOptions::default()
+            },
+            resources: BTreeMap::from_iter([(
+                "foo".to_string(),
+                Resource {
+                    name: Cow::Owned("foo".to_string()),
+                    ..Resource::default()
+                },
+            )]),
+            ..EmbeddedPythonResources::default()
+        };
+
+        let mut module_names = Vec::new();
+        let mut resources = Vec::new();
+        embedded.write_blobs(&mut module_names, &mut resources)?;
+
+        assert!(module_names.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_blobs_leaves_small_resources_uncompressed() -> Result<()> {
+        let embedded = EmbeddedPythonResources {
+            resources_serialization_options: ResourcesSerializationOptions {
+                compress: true,
+                compression_threshold: 16384,
+                ..ResourcesSerializationOptions::default()
+            },
+            ..EmbeddedPythonResources::default()
+        };
+
+        let mut module_names = Vec::new();
+        let mut resources = Vec::new();
+        embedded.write_blobs(&mut module_names, &mut resources)?;
+
+        // An empty resource set never reaches the threshold, so the flag byte must say "raw".
+        assert_eq!(resources[0], 0u8);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_blobs_compresses_resources_above_threshold() -> Result<()> {
+        let mut r =
+            PrePackagedResources::new(&PythonResourcesPolicy::InMemoryOnly, DEFAULT_CACHE_TAG);
+
+        // Use a large, repetitive payload so the compressed form is guaranteed to still be
+        // smaller than the raw resources blob, even once the flag byte and zlib framing
+        // overhead are accounted for.
+        r.add_in_memory_module_source(&PythonModuleSource {
+            name: "foo".to_string(),
+            source: DataLocation::Memory(vec![b'a'; 65536]),
+            is_package: false,
+            cache_tag: DEFAULT_CACHE_TAG.to_string(),
+        })?;
+
+        let pre = r
+            .collector
+            .resources
+            .get("foo")
+            .expect("foo was just added");
+        let resource = Resource::try_from(pre)?;
+
+        let embedded = EmbeddedPythonResources {
+            resources: BTreeMap::from_iter([("foo".to_string(), resource)]),
+            resources_serialization_options: ResourcesSerializationOptions {
+                compress: true,
+                compression_threshold: 16,
+                ..ResourcesSerializationOptions::default()
+            },
+            ..EmbeddedPythonResources::default()
+        };
+
+        let mut module_names = Vec::new();
+        let mut resources = Vec::new();
+        embedded.write_blobs(&mut module_names, &mut resources)?;
+
+        assert_eq!(resources[0], 1u8);
+        assert!(resources.len() < 65536);
+
+        Ok(())
+    }
+
     #[test]
     fn test_add_distribution_extension_module() -> Result<()> {
         let mut r =
@@ -834,7 +2139,8 @@ mod tests {
                 link_system_libraries: BTreeSet::new(),
                 link_static_libraries: BTreeSet::new(),
                 link_dynamic_libraries: BTreeSet::new(),
-                link_external_libraries: BTreeSet::new()
+                link_external_libraries: BTreeSet::new(),
+                licensed_component: None
             })
         );
 
@@ -867,7 +2173,8 @@ mod tests {
                 link_system_libraries: BTreeSet::new(),
                 link_static_libraries: BTreeSet::new(),
                 link_dynamic_libraries: BTreeSet::new(),
-                link_external_libraries: BTreeSet::new()
+                link_external_libraries: BTreeSet::new(),
+                licensed_component: None
             })
         );
 